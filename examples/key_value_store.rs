@@ -8,7 +8,6 @@
 
 use bytes::Bytes;
 use http::{Request, StatusCode};
-use hyper::Server;
 use std::{
     borrow::Cow,
     collections::HashMap,
@@ -18,15 +17,15 @@ use std::{
 };
 use tower::{make::Shared, BoxError, ServiceBuilder};
 use tower_http::{
-    add_extension::AddExtensionLayer, auth::RequireAuthorizationLayer,
-    compression::CompressionLayer, trace::TraceLayer,
+    auth::RequireAuthorizationLayer, compression::CompressionLayer, trace::TraceLayer,
 };
 use tower_web::{
     body::{Body, BoxBody},
-    extract::{BytesMaxLength, Extension, UrlParams},
+    extract::{with_state, BytesMaxLength, State, UrlParams},
     prelude::*,
     response::IntoResponse,
     routing::BoxRoute,
+    Server,
 };
 
 #[tokio::main]
@@ -51,30 +50,33 @@ async fn main() {
             .concurrency_limit(1024)
             .timeout(Duration::from_secs(10))
             .layer(TraceLayer::new_for_http())
-            .layer(AddExtensionLayer::new(SharedState::default()))
+            .layer(with_state(SharedState::default()))
             .into_inner(),
     )
     // Handle errors from middleware
     .handle_error(handle_error);
 
-    // Run our app with hyper
+    // Run our app, draining in-flight requests on SIGTERM/SIGINT
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
     tracing::debug!("listening on {}", addr);
-    let server = Server::bind(&addr).serve(Shared::new(app));
-    server.await.unwrap();
+    Server::bind(&addr)
+        .grace_period(Duration::from_secs(30))
+        .serve(Shared::new(app))
+        .await
+        .unwrap();
 }
 
-type SharedState = Arc<RwLock<State>>;
+type SharedState = Arc<RwLock<AppState>>;
 
 #[derive(Default)]
-struct State {
+struct AppState {
     db: HashMap<String, Bytes>,
 }
 
 async fn kv_get(
     _req: Request<Body>,
     UrlParams((key,)): UrlParams<(String,)>,
-    Extension(state): Extension<SharedState>,
+    State(state): State<SharedState>,
 ) -> Result<Bytes, StatusCode> {
     let db = &state.read().unwrap().db;
 
@@ -89,12 +91,12 @@ async fn kv_set(
     _req: Request<Body>,
     UrlParams((key,)): UrlParams<(String,)>,
     BytesMaxLength(value): BytesMaxLength<{ 1024 * 5_000 }>, // ~5mb
-    Extension(state): Extension<SharedState>,
+    State(state): State<SharedState>,
 ) {
     state.write().unwrap().db.insert(key, value);
 }
 
-async fn list_keys(_req: Request<Body>, Extension(state): Extension<SharedState>) -> String {
+async fn list_keys(_req: Request<Body>, State(state): State<SharedState>) -> String {
     let db = &state.read().unwrap().db;
 
     db.keys()
@@ -104,14 +106,14 @@ async fn list_keys(_req: Request<Body>, Extension(state): Extension<SharedState>
 }
 
 fn admin_routes() -> BoxRoute<BoxBody> {
-    async fn delete_all_keys(_req: Request<Body>, Extension(state): Extension<SharedState>) {
+    async fn delete_all_keys(_req: Request<Body>, State(state): State<SharedState>) {
         state.write().unwrap().db.clear();
     }
 
     async fn remove_key(
         _req: Request<Body>,
         UrlParams((key,)): UrlParams<(String,)>,
-        Extension(state): Extension<SharedState>,
+        State(state): State<SharedState>,
     ) {
         state.write().unwrap().db.remove(&key);
     }
@@ -0,0 +1,438 @@
+//! Extractor for `multipart/form-data` request bodies.
+
+use super::FromRequest;
+use crate::{
+    body::{Body, BoxBody},
+    response::IntoResponse,
+};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use http::{header, Request, Response, StatusCode};
+use http_body::Body as _;
+use std::fmt;
+
+/// Default maximum size of a single field, in bytes.
+const DEFAULT_FIELD_LIMIT: usize = 1024 * 1024;
+
+/// Extractor for `multipart/form-data` request bodies.
+///
+/// The body is parsed lazily, one field at a time, so a large binary value can
+/// be consumed chunk-by-chunk without buffering the whole request. Per-field
+/// and total size limits are enforced as the body is streamed.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use tower_web::{prelude::*, extract::Multipart};
+///
+/// async fn upload(mut multipart: Multipart) {
+///     while let Some(mut field) = multipart.next_field().await.unwrap() {
+///         let name = field.name().map(ToOwned::to_owned);
+///         while let Some(chunk) = field.chunk().await.unwrap() {
+///             // write `chunk` to storage, keyed by `name`
+///             let _ = (&name, chunk);
+///         }
+///     }
+/// }
+///
+/// let app = route("/upload", post(upload));
+/// # async {
+/// # hyper::Server::bind(&"".parse().unwrap()).serve(tower::make::Shared::new(app)).await.unwrap();
+/// # };
+/// ```
+#[derive(Debug)]
+pub struct Multipart {
+    body: Body,
+    boundary: Vec<u8>,
+    buffer: BytesMut,
+    state: State,
+    field_read: usize,
+    total_read: usize,
+    field_limit: usize,
+    total_limit: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum State {
+    /// Haven't consumed the opening boundary yet.
+    Start,
+    /// Positioned at a `--boundary` delimiter that hasn't been consumed.
+    AtBoundary,
+    /// Currently streaming a field's body.
+    InField,
+    /// The closing `--boundary--` has been seen.
+    Eof,
+}
+
+#[async_trait]
+impl FromRequest for Multipart {
+    type Rejection = MultipartRejection;
+
+    async fn from_request(req: &mut Request<Body>) -> Result<Self, Self::Rejection> {
+        let boundary = parse_boundary(req).ok_or(MultipartRejection::InvalidBoundary)?;
+        let body = std::mem::take(req.body_mut());
+
+        Ok(Multipart {
+            body,
+            boundary: format!("--{}", boundary).into_bytes(),
+            buffer: BytesMut::new(),
+            state: State::Start,
+            field_read: 0,
+            total_read: 0,
+            field_limit: DEFAULT_FIELD_LIMIT,
+            total_limit: None,
+        })
+    }
+}
+
+impl Multipart {
+    /// Set the maximum size of a single field, in bytes.
+    pub fn field_limit(mut self, limit: usize) -> Self {
+        self.field_limit = limit;
+        self
+    }
+
+    /// Set the maximum total size of the whole body, in bytes.
+    pub fn total_limit(mut self, limit: usize) -> Self {
+        self.total_limit = Some(limit);
+        self
+    }
+
+    /// Advance to the next field, returning `None` once the body is exhausted.
+    ///
+    /// Any bytes left unread in the previous field are discarded first.
+    pub async fn next_field(&mut self) -> Result<Option<Field<'_>>, MultipartError> {
+        // Drain whatever is left of the current field.
+        while self.state == State::InField {
+            if self.read_chunk().await?.is_none() {
+                break;
+            }
+        }
+
+        // Consume the boundary that separates parts.
+        if self.state == State::Start {
+            self.consume_until_boundary().await?;
+        }
+        if self.state == State::Eof {
+            return Ok(None);
+        }
+
+        match self.consume_boundary().await? {
+            Boundary::Final => {
+                self.state = State::Eof;
+                Ok(None)
+            }
+            Boundary::Next => {
+                let headers = self.read_field_headers().await?;
+                self.state = State::InField;
+                self.field_read = 0;
+                Ok(Some(Field {
+                    multipart: self,
+                    name: headers.name,
+                    file_name: headers.file_name,
+                    content_type: headers.content_type,
+                }))
+            }
+        }
+    }
+
+    /// Pull one chunk of the current field's body, or `None` at the boundary.
+    async fn read_chunk(&mut self) -> Result<Option<Bytes>, MultipartError> {
+        // The field body ends at the boundary; once we've reached it there is
+        // nothing left to yield for this field.
+        if self.state == State::AtBoundary {
+            return Ok(None);
+        }
+
+        let delimiter = delimiter(&self.boundary);
+
+        loop {
+            if let Some(pos) = find(&self.buffer, &delimiter) {
+                let chunk = self.buffer.split_to(pos).freeze();
+                // Drop the leading CRLF of the delimiter; leave `--boundary`.
+                let _ = self.buffer.split_to(2);
+                self.state = State::AtBoundary;
+                if chunk.is_empty() {
+                    return Ok(None);
+                }
+                self.account_field(chunk.len())?;
+                return Ok(Some(chunk));
+            }
+
+            // No delimiter yet: release everything except a possible partial
+            // delimiter at the tail, so we never split a boundary in half.
+            let keep = delimiter.len().saturating_sub(1);
+            if self.buffer.len() > keep {
+                let take = self.buffer.len() - keep;
+                let chunk = self.buffer.split_to(take).freeze();
+                self.account_field(chunk.len())?;
+                return Ok(Some(chunk));
+            }
+
+            if !self.fill().await? {
+                return Err(MultipartError::UnexpectedEof);
+            }
+        }
+    }
+
+    async fn consume_until_boundary(&mut self) -> Result<(), MultipartError> {
+        loop {
+            if let Some(pos) = find(&self.buffer, &self.boundary) {
+                let _ = self.buffer.split_to(pos);
+                self.state = State::AtBoundary;
+                return Ok(());
+            }
+            if !self.fill().await? {
+                return Err(MultipartError::UnexpectedEof);
+            }
+        }
+    }
+
+    async fn consume_boundary(&mut self) -> Result<Boundary, MultipartError> {
+        while self.buffer.len() < self.boundary.len() + 2 {
+            if !self.fill().await? {
+                return Err(MultipartError::UnexpectedEof);
+            }
+        }
+
+        let _ = self.buffer.split_to(self.boundary.len());
+
+        // Either `--` (final boundary) or CRLF (another part follows).
+        if self.buffer.starts_with(b"--") {
+            Ok(Boundary::Final)
+        } else if self.buffer.starts_with(b"\r\n") {
+            let _ = self.buffer.split_to(2);
+            Ok(Boundary::Next)
+        } else {
+            Err(MultipartError::MalformedBoundary)
+        }
+    }
+
+    async fn read_field_headers(&mut self) -> Result<FieldHeaders, MultipartError> {
+        loop {
+            if let Some(pos) = find(&self.buffer, b"\r\n\r\n") {
+                let raw = self.buffer.split_to(pos).freeze();
+                let _ = self.buffer.split_to(4);
+                return parse_field_headers(&raw);
+            }
+            if !self.fill().await? {
+                return Err(MultipartError::UnexpectedEof);
+            }
+        }
+    }
+
+    /// Pull one chunk off the body stream into the buffer.
+    async fn fill(&mut self) -> Result<bool, MultipartError> {
+        match self.body.data().await {
+            Some(Ok(chunk)) => {
+                self.total_read += chunk.len();
+                if let Some(limit) = self.total_limit {
+                    if self.total_read > limit {
+                        return Err(MultipartError::TotalLimitExceeded);
+                    }
+                }
+                self.buffer.extend_from_slice(&chunk);
+                Ok(true)
+            }
+            Some(Err(_)) => Err(MultipartError::BodyError),
+            None => Ok(false),
+        }
+    }
+
+    fn account_field(&mut self, len: usize) -> Result<(), MultipartError> {
+        self.field_read += len;
+        if self.field_read > self.field_limit {
+            return Err(MultipartError::FieldLimitExceeded);
+        }
+        Ok(())
+    }
+}
+
+enum Boundary {
+    Next,
+    Final,
+}
+
+/// A single field of a multipart body.
+pub struct Field<'a> {
+    multipart: &'a mut Multipart,
+    name: Option<String>,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+impl<'a> Field<'a> {
+    /// The field's name, from its `Content-Disposition` header.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The field's filename, if it was an uploaded file.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The field's `Content-Type`, if present.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Pull the next chunk of this field's body, or `None` once it ends.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, MultipartError> {
+        self.multipart.read_chunk().await
+    }
+
+    /// Buffer the entire remaining field body.
+    pub async fn bytes(mut self) -> Result<Bytes, MultipartError> {
+        let mut buf = BytesMut::new();
+        while let Some(chunk) = self.chunk().await? {
+            buf.extend_from_slice(&chunk);
+        }
+        Ok(buf.freeze())
+    }
+}
+
+impl fmt::Debug for Field<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Field")
+            .field("name", &self.name)
+            .field("file_name", &self.file_name)
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+struct FieldHeaders {
+    name: Option<String>,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+fn parse_field_headers(raw: &[u8]) -> Result<FieldHeaders, MultipartError> {
+    let text = std::str::from_utf8(raw).map_err(|_| MultipartError::MalformedHeaders)?;
+
+    let mut name = None;
+    let mut file_name = None;
+    let mut content_type = None;
+
+    for line in text.split("\r\n").filter(|line| !line.is_empty()) {
+        let (header, value) = line.split_once(':').ok_or(MultipartError::MalformedHeaders)?;
+        let value = value.trim();
+
+        if header.eq_ignore_ascii_case("content-disposition") {
+            name = param(value, "name");
+            file_name = param(value, "filename");
+        } else if header.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value.to_owned());
+        }
+    }
+
+    Ok(FieldHeaders {
+        name,
+        file_name,
+        content_type,
+    })
+}
+
+/// Pull a `key="value"` parameter out of a header value.
+fn param(value: &str, key: &str) -> Option<String> {
+    value.split(';').find_map(|part| {
+        let (k, v) = part.trim().split_once('=')?;
+        if k.eq_ignore_ascii_case(key) {
+            Some(v.trim().trim_matches('"').to_owned())
+        } else {
+            None
+        }
+    })
+}
+
+fn parse_boundary(req: &Request<Body>) -> Option<String> {
+    let content_type = req.headers().get(header::CONTENT_TYPE)?.to_str().ok()?;
+    let mime = content_type.parse::<mime::Mime>().ok()?;
+    if mime.type_() != mime::MULTIPART || mime.subtype() != mime::FORM_DATA {
+        return None;
+    }
+    mime.get_param(mime::BOUNDARY).map(|b| b.as_str().to_owned())
+}
+
+/// The delimiter that precedes each boundary inside the body: `\r\n--boundary`.
+fn delimiter(boundary: &[u8]) -> Vec<u8> {
+    let mut delimiter = Vec::with_capacity(boundary.len() + 2);
+    delimiter.extend_from_slice(b"\r\n");
+    delimiter.extend_from_slice(boundary);
+    delimiter
+}
+
+/// Naive substring search; the haystack is only ever a small streaming buffer.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() || haystack.len() < needle.len() {
+        return None;
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Errors produced while streaming a multipart body.
+#[derive(Debug)]
+pub enum MultipartError {
+    /// A field exceeded the configured per-field limit.
+    FieldLimitExceeded,
+    /// The body exceeded the configured total limit.
+    TotalLimitExceeded,
+    /// The body ended before a closing boundary was seen.
+    UnexpectedEof,
+    /// A boundary marker was malformed.
+    MalformedBoundary,
+    /// A field's headers were malformed.
+    MalformedHeaders,
+    /// The underlying body stream errored.
+    BodyError,
+}
+
+impl fmt::Display for MultipartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultipartError::FieldLimitExceeded => f.write_str("field exceeded its size limit"),
+            MultipartError::TotalLimitExceeded => f.write_str("body exceeded its size limit"),
+            MultipartError::UnexpectedEof => f.write_str("unexpected end of multipart body"),
+            MultipartError::MalformedBoundary => f.write_str("malformed multipart boundary"),
+            MultipartError::MalformedHeaders => f.write_str("malformed multipart field headers"),
+            MultipartError::BodyError => f.write_str("error reading request body"),
+        }
+    }
+}
+
+impl std::error::Error for MultipartError {}
+
+impl IntoResponse for MultipartError {
+    fn into_response(self) -> Response<BoxBody> {
+        let status = match self {
+            MultipartError::FieldLimitExceeded | MultipartError::TotalLimitExceeded => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            MultipartError::BodyError => StatusCode::BAD_REQUEST,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// Rejection returned by the [`Multipart`] extractor when the request isn't a
+/// well-formed `multipart/form-data` request.
+#[derive(Debug)]
+pub enum MultipartRejection {
+    /// The `Content-Type` was missing or not `multipart/form-data` with a
+    /// `boundary`.
+    InvalidBoundary,
+}
+
+impl IntoResponse for MultipartRejection {
+    fn into_response(self) -> Response<BoxBody> {
+        (
+            StatusCode::BAD_REQUEST,
+            "Expected a `multipart/form-data` request with a boundary".to_string(),
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,145 @@
+//! Extractor for the application state carried by the router.
+
+use super::FromRequest;
+use crate::body::Body;
+use async_trait::async_trait;
+use http::Request;
+use std::{
+    convert::Infallible,
+    ops::{Deref, DerefMut},
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+
+/// Extractor for the state carried by the router.
+///
+/// The state is attached with [`with_state`] and cloned out for each request.
+/// Handlers receive the value directly instead of reaching for an [`Extension`]
+/// and unwrapping it by hand.
+///
+/// [`Extension`]: super::Extension
+///
+/// # Missing state
+///
+/// The state is threaded through the request extensions by [`with_state`], so
+/// extraction panics if that layer is not on the router. This shares the
+/// failure mode of [`Extension`]: a forgotten layer is a runtime error, not a
+/// compile-time one.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use tower_web::{prelude::*, extract::{State, with_state}};
+/// use std::sync::{Arc, RwLock};
+///
+/// #[derive(Clone, Default)]
+/// struct AppState {
+///     db: Arc<RwLock<std::collections::HashMap<String, String>>>,
+/// }
+///
+/// async fn list_keys(_req: http::Request<tower_web::body::Body>, State(state): State<AppState>) -> String {
+///     state.db.read().unwrap().keys().cloned().collect::<Vec<_>>().join("\n")
+/// }
+///
+/// let app = route("/keys", get(list_keys)).layer(with_state(AppState::default()));
+/// # async {
+/// # hyper::Server::bind(&"".parse().unwrap()).serve(tower::make::Shared::new(app)).await.unwrap();
+/// # };
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct State<S>(pub S);
+
+#[async_trait]
+impl<S> FromRequest for State<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    type Rejection = Infallible;
+
+    async fn from_request(req: &mut Request<Body>) -> Result<Self, Self::Rejection> {
+        // `with_state` injects the state into the request extensions; if that
+        // layer is missing from the router there is nothing to clone and this
+        // panics.
+        let state = req
+            .extensions()
+            .get::<State<S>>()
+            .expect("state not injected; add `with_state` to the router")
+            .clone();
+        Ok(state)
+    }
+}
+
+impl<S> Deref for State<S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<S> DerefMut for State<S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Attach application state to the router.
+///
+/// The returned [`Layer`] owns `state` and clones it into every request as it
+/// is routed, where [`State`] picks it up. This is the builder half of the
+/// state API: handlers take `State(state): State<S>` instead of smuggling the
+/// value through [`Extension`].
+///
+/// [`Extension`]: super::Extension
+pub fn with_state<S>(state: S) -> StateLayer<S>
+where
+    S: Clone,
+{
+    StateLayer { state }
+}
+
+/// [`Layer`] produced by [`with_state`] that injects the router state.
+#[derive(Debug, Clone, Copy)]
+pub struct StateLayer<S> {
+    state: S,
+}
+
+impl<S, Inner> Layer<Inner> for StateLayer<S>
+where
+    S: Clone,
+{
+    type Service = AddState<S, Inner>;
+
+    fn layer(&self, inner: Inner) -> Self::Service {
+        AddState {
+            state: self.state.clone(),
+            inner,
+        }
+    }
+}
+
+/// Middleware created by [`StateLayer`] that clones the state into each request.
+#[derive(Debug, Clone, Copy)]
+pub struct AddState<S, Inner> {
+    state: S,
+    inner: Inner,
+}
+
+impl<S, Inner, ReqBody> Service<Request<ReqBody>> for AddState<S, Inner>
+where
+    S: Clone + Send + Sync + 'static,
+    Inner: Service<Request<ReqBody>>,
+{
+    type Response = Inner::Response;
+    type Error = Inner::Error;
+    type Future = Inner::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        req.extensions_mut().insert(State(self.state.clone()));
+        self.inner.call(req)
+    }
+}
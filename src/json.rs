@@ -0,0 +1,671 @@
+//! JSON extractor and response.
+
+use crate::{
+    body::{box_body, Body, BoxBody},
+    extract::FromRequest,
+    response::IntoResponse,
+};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use http::{header, Request, Response, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// JSON extractor and response.
+///
+/// # As an extractor
+///
+/// Buffers the request body (up to `N` bytes, defaulting to
+/// [`JSON_MAX_LENGTH`]), checks that the `Content-Type` is `application/json`
+/// and deserializes it into `T`. The limit is a const generic, so a handler
+/// expecting larger bodies can raise it per-extractor like `Json<T, { 1024 *
+/// 1024 }>`, mirroring [`BytesMaxLength`](crate::extract::BytesMaxLength).
+///
+/// When deserialization fails the error carries the JSON pointer to the
+/// offending field, so callers get an actionable `422 Unprocessable Entity`
+/// body such as `Failed to deserialize at .items[2].key: invalid type`.
+///
+/// # As a response
+///
+/// Serializes `T` to JSON and sets `Content-Type: application/json`.
+///
+/// ```rust,no_run
+/// use tower_web::{prelude::*, extract::Json};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize)]
+/// struct SetKey {
+///     key: String,
+///     value: String,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Stored {
+///     key: String,
+/// }
+///
+/// async fn set(Json(payload): Json<SetKey>) -> Json<Stored> {
+///     Json(Stored { key: payload.key })
+/// }
+///
+/// let app = route("/kv", post(set));
+/// # async {
+/// # hyper::Server::bind(&"".parse().unwrap()).serve(tower::make::Shared::new(app)).await.unwrap();
+/// # };
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T, const N: u64 = JSON_MAX_LENGTH>(pub T);
+
+/// The default number of bytes [`Json`] will buffer when used as an extractor.
+pub const JSON_MAX_LENGTH: u64 = 1024 * 256;
+
+#[async_trait]
+impl<T, const N: u64> FromRequest for Json<T, N>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = JsonRejection;
+
+    async fn from_request(req: &mut Request<Body>) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(req) {
+            return Err(JsonRejection::MissingJsonContentType);
+        }
+
+        let body = std::mem::take(req.body_mut());
+        let bytes = buffer_body(body, N).await?;
+
+        let mut deserializer = serde_json::Deserializer::from_slice(&bytes);
+        let value = path::deserialize(&mut deserializer).map_err(JsonRejection::Deserialize)?;
+        deserializer
+            .end()
+            .map_err(|err| JsonRejection::Deserialize(path::Error::trailing(err)))?;
+
+        Ok(Json(value))
+    }
+}
+
+impl<T, const N: u64> IntoResponse for Json<T, N>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response<BoxBody> {
+        let bytes = match serde_json::to_vec(&self.0) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to serialize response body: {}", err),
+                )
+                    .into_response()
+            }
+        };
+
+        let mut res = Response::new(box_body(Body::from(bytes)));
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/json"),
+        );
+        res
+    }
+}
+
+fn has_json_content_type(req: &Request<Body>) -> bool {
+    req.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<mime::Mime>().ok())
+        .map(|mime| {
+            mime.type_() == "application"
+                && (mime.subtype() == "json" || mime.suffix().map_or(false, |s| s == "json"))
+        })
+        .unwrap_or(false)
+}
+
+async fn buffer_body(mut body: Body, max_length: u64) -> Result<Bytes, JsonRejection> {
+    use http_body::Body as _;
+
+    let mut buf = BytesMut::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| JsonRejection::BodyError)?;
+        if buf.len() as u64 + chunk.len() as u64 > max_length {
+            return Err(JsonRejection::LengthLimitExceeded);
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(buf.freeze())
+}
+
+/// Rejection used by the [`Json`] extractor.
+#[derive(Debug)]
+pub enum JsonRejection {
+    /// The request did not have a `Content-Type: application/json` header.
+    MissingJsonContentType,
+    /// The request body exceeded the configured length limit.
+    LengthLimitExceeded,
+    /// The body could not be read.
+    BodyError,
+    /// The body was not valid JSON for `T`.
+    Deserialize(path::Error),
+}
+
+impl IntoResponse for JsonRejection {
+    fn into_response(self) -> Response<BoxBody> {
+        match self {
+            JsonRejection::MissingJsonContentType => (
+                StatusCode::BAD_REQUEST,
+                "Expected request with `Content-Type: application/json`".to_string(),
+            )
+                .into_response(),
+            JsonRejection::LengthLimitExceeded => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Request body too large".to_string(),
+            )
+                .into_response(),
+            JsonRejection::BodyError => (
+                StatusCode::BAD_REQUEST,
+                "Failed to read request body".to_string(),
+            )
+                .into_response(),
+            JsonRejection::Deserialize(err) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, err.to_string()).into_response()
+            }
+        }
+    }
+}
+
+/// Path-tracking wrapper around a [`serde::Deserializer`].
+///
+/// As deserialization descends into maps and sequences a stack of
+/// [`Segment`]s is pushed and popped. If the inner deserializer fails the
+/// path in effect at that point is captured and rendered as a JSON pointer
+/// alongside the original message.
+pub mod path {
+    use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+    use std::{
+        cell::RefCell,
+        fmt::{self, Display},
+    };
+
+    /// One frame of the path to the value currently being deserialized.
+    #[derive(Debug, Clone)]
+    pub enum Segment {
+        /// A map key, e.g. `.key`.
+        Map(String),
+        /// A sequence index, e.g. `[2]`.
+        Seq(usize),
+    }
+
+    /// A node in the path stack, linked to its parent on the call stack so no
+    /// allocation is needed while descending.
+    struct Chain<'a> {
+        parent: Option<&'a Chain<'a>>,
+        segment: Segment,
+    }
+
+    impl<'a> Chain<'a> {
+        fn path(&self) -> Vec<Segment> {
+            let mut segments = match self.parent {
+                Some(parent) => parent.path(),
+                None => Vec::new(),
+            };
+            segments.push(self.segment.clone());
+            segments
+        }
+    }
+
+    /// Where the deepest error records the path it failed at.
+    #[derive(Default)]
+    struct Track {
+        path: RefCell<Option<Vec<Segment>>>,
+    }
+
+    impl Track {
+        fn record(&self, chain: Option<&Chain<'_>>) {
+            // Only the innermost failure wins. The error bubbles up from the
+            // leaf, so the first frame to record is the deepest one; peek
+            // without consuming so outer frames (and the duplicate record on
+            // the way out) don't clobber it.
+            if self.path.borrow().is_none() {
+                *self.path.borrow_mut() = Some(chain.map(Chain::path).unwrap_or_default());
+            }
+        }
+    }
+
+    /// An error that pairs serde's message with the path it occurred at.
+    #[derive(Debug)]
+    pub struct Error {
+        path: Vec<Segment>,
+        message: String,
+        trailing: bool,
+    }
+
+    impl Error {
+        pub(super) fn trailing(err: serde_json::Error) -> Self {
+            Error {
+                path: Vec::new(),
+                message: err.to_string(),
+                trailing: true,
+            }
+        }
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if self.trailing {
+                return write!(f, "Failed to deserialize: {}", self.message);
+            }
+
+            write!(f, "Failed to deserialize at ")?;
+            if self.path.is_empty() {
+                write!(f, ".")?;
+            } else {
+                for segment in &self.path {
+                    match segment {
+                        Segment::Map(key) => write!(f, ".{}", key)?,
+                        Segment::Seq(index) => write!(f, "[{}]", index)?,
+                    }
+                }
+            }
+            write!(f, ": {}", self.message)
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    /// Deserialize `T` while tracking the path to any error.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+        D::Error: Display,
+    {
+        let track = Track::default();
+        let result = T::deserialize(PathDeserializer {
+            de: deserializer,
+            chain: None,
+            track: &track,
+        });
+
+        result.map_err(|err| Error {
+            path: track.path.into_inner().unwrap_or_default(),
+            message: err.to_string(),
+            trailing: false,
+        })
+    }
+
+    struct PathDeserializer<'a, 'b, D> {
+        de: D,
+        chain: Option<&'a Chain<'a>>,
+        track: &'b Track,
+    }
+
+    /// Forward every `deserialize_*` method to the inner deserializer, wrapping
+    /// the visitor so that nested maps and sequences keep the path up to date.
+    macro_rules! forward {
+        ($($method:ident),* $(,)?) => {
+            $(
+                fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+                where
+                    V: Visitor<'de>,
+                {
+                    let chain = self.chain;
+                    let track = self.track;
+                    self.de
+                        .$method(PathVisitor { visitor, chain, track })
+                        .map_err(|err| { track.record(chain); err })
+                }
+            )*
+        };
+    }
+
+    impl<'de, 'a, 'b, D> Deserializer<'de> for PathDeserializer<'a, 'b, D>
+    where
+        D: Deserializer<'de>,
+    {
+        type Error = D::Error;
+
+        forward! {
+            deserialize_any, deserialize_bool, deserialize_i8, deserialize_i16,
+            deserialize_i32, deserialize_i64, deserialize_i128, deserialize_u8, deserialize_u16,
+            deserialize_u32, deserialize_u64, deserialize_u128, deserialize_f32, deserialize_f64,
+            deserialize_char, deserialize_str, deserialize_string, deserialize_bytes,
+            deserialize_byte_buf, deserialize_option, deserialize_unit, deserialize_seq,
+            deserialize_map, deserialize_identifier, deserialize_ignored_any,
+        }
+
+        fn deserialize_unit_struct<V>(
+            self,
+            name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let (chain, track) = (self.chain, self.track);
+            self.de
+                .deserialize_unit_struct(name, PathVisitor { visitor, chain, track })
+                .map_err(|err| {
+                    track.record(chain);
+                    err
+                })
+        }
+
+        fn deserialize_newtype_struct<V>(
+            self,
+            name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let (chain, track) = (self.chain, self.track);
+            self.de
+                .deserialize_newtype_struct(name, PathVisitor { visitor, chain, track })
+                .map_err(|err| {
+                    track.record(chain);
+                    err
+                })
+        }
+
+        fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let (chain, track) = (self.chain, self.track);
+            self.de
+                .deserialize_tuple(len, PathVisitor { visitor, chain, track })
+                .map_err(|err| {
+                    track.record(chain);
+                    err
+                })
+        }
+
+        fn deserialize_tuple_struct<V>(
+            self,
+            name: &'static str,
+            len: usize,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let (chain, track) = (self.chain, self.track);
+            self.de
+                .deserialize_tuple_struct(name, len, PathVisitor { visitor, chain, track })
+                .map_err(|err| {
+                    track.record(chain);
+                    err
+                })
+        }
+
+        fn deserialize_struct<V>(
+            self,
+            name: &'static str,
+            fields: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let (chain, track) = (self.chain, self.track);
+            self.de
+                .deserialize_struct(name, fields, PathVisitor { visitor, chain, track })
+                .map_err(|err| {
+                    track.record(chain);
+                    err
+                })
+        }
+
+        fn deserialize_enum<V>(
+            self,
+            name: &'static str,
+            variants: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let (chain, track) = (self.chain, self.track);
+            self.de
+                .deserialize_enum(name, variants, PathVisitor { visitor, chain, track })
+                .map_err(|err| {
+                    track.record(chain);
+                    err
+                })
+        }
+    }
+
+    struct PathVisitor<'a, 'b, V> {
+        visitor: V,
+        chain: Option<&'a Chain<'a>>,
+        track: &'b Track,
+    }
+
+    impl<'de, 'a, 'b, V> Visitor<'de> for PathVisitor<'a, 'b, V>
+    where
+        V: Visitor<'de>,
+    {
+        type Value = V::Value;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.visitor.expecting(formatter)
+        }
+
+        fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            self.visitor.visit_seq(PathSeqAccess {
+                seq,
+                chain: self.chain,
+                track: self.track,
+                index: 0,
+            })
+        }
+
+        fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            self.visitor.visit_map(PathMapAccess {
+                map,
+                chain: self.chain,
+                track: self.track,
+                key: None,
+            })
+        }
+    }
+
+    struct PathSeqAccess<'a, 'b, A> {
+        seq: A,
+        chain: Option<&'a Chain<'a>>,
+        track: &'b Track,
+        index: usize,
+    }
+
+    impl<'de, 'a, 'b, A> SeqAccess<'de> for PathSeqAccess<'a, 'b, A>
+    where
+        A: SeqAccess<'de>,
+    {
+        type Error = A::Error;
+
+        fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+        where
+            T: DeserializeSeed<'de>,
+        {
+            let chain = Chain {
+                parent: self.chain,
+                segment: Segment::Seq(self.index),
+            };
+            self.index += 1;
+            self.seq.next_element_seed(PathSeed {
+                seed,
+                chain: Some(&chain),
+                track: self.track,
+            })
+        }
+    }
+
+    struct PathMapAccess<'a, 'b, A> {
+        map: A,
+        chain: Option<&'a Chain<'a>>,
+        track: &'b Track,
+        key: Option<String>,
+    }
+
+    impl<'de, 'a, 'b, A> MapAccess<'de> for PathMapAccess<'a, 'b, A>
+    where
+        A: MapAccess<'de>,
+    {
+        type Error = A::Error;
+
+        fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+        where
+            K: DeserializeSeed<'de>,
+        {
+            self.map.next_key_seed(CaptureKey {
+                seed,
+                out: &mut self.key,
+            })
+        }
+
+        fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+        where
+            V: DeserializeSeed<'de>,
+        {
+            let chain = Chain {
+                parent: self.chain,
+                segment: Segment::Map(self.key.clone().unwrap_or_default()),
+            };
+            self.map.next_value_seed(PathSeed {
+                seed,
+                chain: Some(&chain),
+                track: self.track,
+            })
+        }
+    }
+
+    /// Capture a map key as a string for use in the path while still handing
+    /// the key off to the real seed for deserialization.
+    struct CaptureKey<'o, K> {
+        seed: K,
+        out: &'o mut Option<String>,
+    }
+
+    impl<'de, 'o, K> DeserializeSeed<'de> for CaptureKey<'o, K>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        type Value = K::Value;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            self.seed.deserialize(KeyDeserializer {
+                de: deserializer,
+                out: self.out,
+            })
+        }
+    }
+
+    /// Forwards to the inner key deserializer but records the textual key so it
+    /// can be rendered in the error path.
+    struct KeyDeserializer<'o, D> {
+        de: D,
+        out: &'o mut Option<String>,
+    }
+
+    impl<'de, 'o, D> Deserializer<'de> for KeyDeserializer<'o, D>
+    where
+        D: Deserializer<'de>,
+    {
+        type Error = D::Error;
+
+        fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.de.deserialize_any(KeyVisitor {
+                visitor,
+                out: self.out,
+            })
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
+    struct KeyVisitor<'o, V> {
+        visitor: V,
+        out: &'o mut Option<String>,
+    }
+
+    impl<'de, 'o, V> Visitor<'de> for KeyVisitor<'o, V>
+    where
+        V: Visitor<'de>,
+    {
+        type Value = V::Value;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.visitor.expecting(formatter)
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            *self.out = Some(value.to_owned());
+            self.visitor.visit_str(value)
+        }
+
+        fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            *self.out = Some(value.clone());
+            self.visitor.visit_string(value)
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            *self.out = Some(value.to_string());
+            self.visitor.visit_u64(value)
+        }
+    }
+
+    struct PathSeed<'a, 'b, S> {
+        seed: S,
+        chain: Option<&'a Chain<'a>>,
+        track: &'b Track,
+    }
+
+    impl<'de, 'a, 'b, S> DeserializeSeed<'de> for PathSeed<'a, 'b, S>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        type Value = S::Value;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let chain = self.chain;
+            let track = self.track;
+            self.seed
+                .deserialize(PathDeserializer {
+                    de: deserializer,
+                    chain,
+                    track,
+                })
+                .map_err(|err| {
+                    track.record(chain);
+                    err
+                })
+        }
+    }
+}
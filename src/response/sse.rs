@@ -0,0 +1,334 @@
+//! Server-Sent Events (SSE) responses.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use tower_web::{prelude::*, response::sse::{Sse, Event, KeepAlive}};
+//! use futures_util::stream::{self, Stream, StreamExt};
+//! use tokio_stream::StreamExt as _;
+//! use std::{convert::Infallible, time::Duration};
+//!
+//! async fn notifications() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+//!     let stream = stream::repeat_with(|| Event::default().data("tick"))
+//!         .map(Ok)
+//!         .throttle(Duration::from_secs(1));
+//!
+//!     Sse::new(stream).keep_alive(KeepAlive::new())
+//! }
+//!
+//! let app = route("/sse", get(notifications));
+//! # async {
+//! # hyper::Server::bind(&"".parse().unwrap()).serve(tower::make::Shared::new(app)).await.unwrap();
+//! # };
+//! ```
+
+use crate::{
+    body::{box_body, BoxBody},
+    response::IntoResponse,
+};
+use bytes::Bytes;
+use futures_util::{
+    ready,
+    stream::{Stream, TryStream},
+};
+use http::{header, Response, StatusCode};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{Instant, Sleep};
+
+/// An SSE response.
+///
+/// See the [module docs](self) for an example.
+#[derive(Clone)]
+pub struct Sse<S> {
+    stream: S,
+    keep_alive: Option<KeepAlive>,
+}
+
+impl<S> Sse<S> {
+    /// Create a new [`Sse`] response that will respond with the given stream of
+    /// [`Event`]s.
+    ///
+    /// See the [module docs](self) for more details.
+    pub fn new(stream: S) -> Self
+    where
+        S: TryStream<Ok = Event> + Send + 'static,
+        S::Error: Into<crate::Error>,
+    {
+        Sse {
+            stream,
+            keep_alive: None,
+        }
+    }
+
+    /// Configure the interval between keep-alive messages.
+    ///
+    /// Defaults to not sending keep-alive messages.
+    pub fn keep_alive(mut self, keep_alive: KeepAlive) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+}
+
+impl<S> fmt::Debug for Sse<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sse")
+            .field("stream", &format_args!("{}", std::any::type_name::<S>()))
+            .field("keep_alive", &self.keep_alive)
+            .finish()
+    }
+}
+
+impl<S, E> IntoResponse for Sse<S>
+where
+    S: Stream<Item = Result<Event, E>> + Send + 'static,
+    E: Into<crate::Error>,
+{
+    fn into_response(self) -> Response<BoxBody> {
+        let body = SseBody {
+            event_stream: self.stream,
+            keep_alive: self.keep_alive.map(KeepAliveStream::new),
+        };
+
+        let mut res = Response::new(box_body(body));
+        *res.status_mut() = StatusCode::OK;
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("text/event-stream"),
+        );
+        res.headers_mut().insert(
+            header::CACHE_CONTROL,
+            header::HeaderValue::from_static("no-cache"),
+        );
+        res
+    }
+}
+
+#[pin_project]
+struct SseBody<S> {
+    #[pin]
+    event_stream: S,
+    #[pin]
+    keep_alive: Option<KeepAliveStream>,
+}
+
+impl<S, E> http_body::Body for SseBody<S>
+where
+    S: Stream<Item = Result<Event, E>>,
+    E: Into<crate::Error>,
+{
+    type Data = Bytes;
+    type Error = E;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = self.project();
+
+        match this.event_stream.poll_next(cx) {
+            Poll::Pending => {
+                if let Some(keep_alive) = this.keep_alive.as_pin_mut() {
+                    keep_alive.poll_event(cx).map(|e| Some(Ok(e.finalize())))
+                } else {
+                    Poll::Pending
+                }
+            }
+            Poll::Ready(Some(Ok(event))) => {
+                if let Some(keep_alive) = this.keep_alive.as_pin_mut() {
+                    keep_alive.reset();
+                }
+                Poll::Ready(Some(Ok(event.finalize())))
+            }
+            Poll::Ready(Some(Err(error))) => Poll::Ready(Some(Err(error))),
+            Poll::Ready(None) => Poll::Ready(None),
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
+}
+
+/// A single server-sent event.
+#[derive(Default, Debug, Clone)]
+pub struct Event {
+    name: Option<String>,
+    id: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    /// Set the event's payload (the `data:` field).
+    ///
+    /// Payloads containing newlines are split into one `data:` field per line,
+    /// as required by the SSE wire format.
+    pub fn data<T>(mut self, data: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Set the event's name (the `event:` field).
+    pub fn event<T>(mut self, name: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set the event's identifier (the `id:` field).
+    pub fn id<T>(mut self, id: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Set the client reconnection time (the `retry:` field), in milliseconds.
+    pub fn retry(mut self, duration: Duration) -> Self {
+        self.retry = Some(duration);
+        self
+    }
+
+    /// A bare comment event, used for keep-alive messages.
+    fn comment<T>(comment: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Event {
+            comment: Some(comment.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Serialize the event into its SSE wire representation.
+    fn finalize(self) -> Bytes {
+        let mut buf = String::new();
+
+        if let Some(comment) = &self.comment {
+            buf.push(':');
+            buf.push_str(comment);
+            buf.push('\n');
+        }
+
+        if let Some(name) = &self.name {
+            buf.push_str("event:");
+            buf.push_str(name);
+            buf.push('\n');
+        }
+
+        if let Some(data) = &self.data {
+            for line in data.split('\n') {
+                buf.push_str("data:");
+                buf.push_str(line);
+                buf.push('\n');
+            }
+        }
+
+        if let Some(id) = &self.id {
+            buf.push_str("id:");
+            buf.push_str(id);
+            buf.push('\n');
+        }
+
+        if let Some(retry) = self.retry {
+            buf.push_str("retry:");
+            buf.push_str(&retry.as_millis().to_string());
+            buf.push('\n');
+        }
+
+        buf.push('\n');
+        Bytes::from(buf)
+    }
+}
+
+/// Configure the interval between keep-alive messages.
+///
+/// While the event stream is idle a comment line (`:\n\n`) is injected on the
+/// configured interval so that proxies don't drop the connection.
+#[derive(Debug, Clone)]
+pub struct KeepAlive {
+    interval: Duration,
+    comment: String,
+}
+
+impl KeepAlive {
+    /// Create a new `KeepAlive` that sends a comment every 15 seconds.
+    pub fn new() -> Self {
+        KeepAlive {
+            interval: Duration::from_secs(15),
+            comment: String::new(),
+        }
+    }
+
+    /// Set how often a keep-alive message should be sent.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the text of the comment sent as a keep-alive message.
+    pub fn comment<T>(mut self, comment: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.comment = comment.into();
+        self
+    }
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[pin_project]
+struct KeepAliveStream {
+    keep_alive: KeepAlive,
+    #[pin]
+    sleep: Sleep,
+}
+
+impl KeepAliveStream {
+    fn new(keep_alive: KeepAlive) -> Self {
+        KeepAliveStream {
+            sleep: tokio::time::sleep(keep_alive.interval),
+            keep_alive,
+        }
+    }
+
+    fn reset(self: Pin<&mut Self>) {
+        let this = self.project();
+        this.sleep
+            .reset(Instant::now() + this.keep_alive.interval);
+    }
+
+    fn poll_event(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Event> {
+        let this = self.project();
+
+        ready!(this.sleep.as_mut().poll(cx));
+
+        let event = Event::comment(this.keep_alive.comment.clone());
+        this.sleep
+            .reset(Instant::now() + this.keep_alive.interval);
+
+        Poll::Ready(event)
+    }
+}
@@ -0,0 +1,167 @@
+//! Serving with graceful shutdown.
+//!
+//! [`Server`] wraps the hyper serving path so that on `SIGTERM`/`SIGINT` the
+//! acceptor stops taking new connections and in-flight requests are allowed to
+//! drain, up to a configurable grace period, before the process exits. This
+//! gives a key/value store somewhere to run a flush-on-exit or to close a
+//! connection pool before it dies.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use tower_web::{prelude::*, Server};
+//! use std::time::Duration;
+//!
+//! # async {
+//! let app = route("/", get(|_: http::Request<tower_web::body::Body>| async { "ok" }));
+//!
+//! Server::bind(&"127.0.0.1:3000".parse().unwrap())
+//!     .grace_period(Duration::from_secs(30))
+//!     .serve(tower::make::Shared::new(app))
+//!     .await
+//!     .unwrap();
+//! # };
+//! ```
+
+use hyper::server::conn::AddrIncoming;
+use std::{future::Future, net::SocketAddr, time::Duration};
+
+/// A serving wrapper that adds signal-driven graceful shutdown on top of
+/// [`hyper::Server`].
+///
+/// See the [module docs](self) for an example.
+#[derive(Debug, Clone)]
+pub struct Server {
+    addr: SocketAddr,
+    grace_period: Option<Duration>,
+}
+
+impl Server {
+    /// Bind to the given address.
+    pub fn bind(addr: &SocketAddr) -> Self {
+        Server {
+            addr: *addr,
+            grace_period: None,
+        }
+    }
+
+    /// Set how long in-flight requests are given to finish after shutdown
+    /// begins. Once the period elapses any remaining connections are
+    /// force-closed.
+    ///
+    /// Defaults to waiting indefinitely for requests to drain.
+    pub fn grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = Some(grace_period);
+        self
+    }
+
+    /// Serve `make_service`, shutting down on `SIGTERM`/`SIGINT`.
+    pub async fn serve<M, S, B>(self, make_service: M) -> hyper::Result<()>
+    where
+        M: for<'a> tower::Service<&'a hyper::server::conn::AddrStream, Response = S, Error = std::convert::Infallible>
+            + Send
+            + 'static,
+        for<'a> <M as tower::Service<&'a hyper::server::conn::AddrStream>>::Future: Send,
+        S: tower::Service<http::Request<hyper::Body>, Response = http::Response<B>, Error = std::convert::Infallible>
+            + Send
+            + 'static,
+        S::Future: Send,
+        B: http_body::Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<crate::Error>,
+    {
+        self.serve_with_shutdown(make_service, shutdown_signal()).await
+    }
+
+    /// Like [`serve`](Self::serve) but driven by a caller-provided shutdown
+    /// future instead of the default signal handler.
+    pub async fn serve_with_shutdown<M, S, B, F>(
+        self,
+        make_service: M,
+        shutdown: F,
+    ) -> hyper::Result<()>
+    where
+        M: for<'a> tower::Service<&'a hyper::server::conn::AddrStream, Response = S, Error = std::convert::Infallible>
+            + Send
+            + 'static,
+        for<'a> <M as tower::Service<&'a hyper::server::conn::AddrStream>>::Future: Send,
+        S: tower::Service<http::Request<hyper::Body>, Response = http::Response<B>, Error = std::convert::Infallible>
+            + Send
+            + 'static,
+        S::Future: Send,
+        B: http_body::Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<crate::Error>,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let incoming = AddrIncoming::bind(&self.addr)?;
+        let grace_period = self.grace_period;
+
+        // Fires once the shutdown future resolves so the grace-period clock can
+        // start at the signal rather than at `serve()`.
+        let (signalled_tx, signalled_rx) = tokio::sync::oneshot::channel();
+        let shutdown = async move {
+            shutdown.await;
+            let _ = signalled_tx.send(());
+        };
+
+        // Stops accepting new connections as soon as `shutdown` resolves and
+        // then drains the in-flight requests hyper is tracking.
+        let graceful = hyper::Server::builder(incoming)
+            .serve(make_service)
+            .with_graceful_shutdown(shutdown);
+
+        match grace_period {
+            Some(grace_period) => {
+                tokio::pin!(graceful);
+
+                // The timer only begins counting down once the shutdown signal
+                // has fired, so in-flight requests get the full grace period to
+                // drain before connections are force-closed.
+                let deadline = async {
+                    let _ = signalled_rx.await;
+                    tokio::time::sleep(grace_period).await;
+                };
+
+                tokio::select! {
+                    result = &mut graceful => result,
+                    _ = deadline => {
+                        tracing::warn!(
+                            "grace period of {:?} elapsed, force-closing remaining connections",
+                            grace_period
+                        );
+                        Ok(())
+                    }
+                }
+            }
+            None => graceful.await,
+        }
+    }
+}
+
+/// A future that resolves when the process receives `SIGTERM` or `SIGINT`.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+
+    tracing::debug!("received shutdown signal, starting graceful shutdown");
+}